@@ -1,4 +1,5 @@
 use getopts;
+use marcutil::query::QueryMatchMut;
 use marcutil::Record;
 use std::env;
 
@@ -17,14 +18,20 @@ fn main() {
     if xml_file_op.is_some() {
         let xml_filename = xml_file_op.unwrap();
 
-        let mut record = Record::from_xml_file(&xml_filename).expect("MARCXML File Parse");
+        let mut record = Record::from_xml_file(&xml_filename)
+            .expect("MARCXML File Parse")
+            .next()
+            .expect("At least one record in the file")
+            .expect("Valid MARCXML record");
 
         if let Some(title) = record.get_values("245", "a").first() {
             println!("Maintitle => {title}");
         }
 
-        if let Some(field) = record.get_fields_mut("245").first_mut() {
-            if let Some(sf) = field.get_subfields_mut("a").first_mut() {
+        if let QueryMatchMut::Subfields(subfields) =
+            record.query_mut("245$a").expect("Valid field spec")
+        {
+            if let Some(sf) = subfields.into_iter().next() {
                 sf.set_content("I Prefer This Title");
             }
         }
@@ -59,11 +66,14 @@ fn main() {
     }
 
     if bin_file_op.is_some() {
-        for record in Record::from_binary_file(&bin_file_op.unwrap()).expect("Start Binary File") {
-            println!(
-                "\nBinary record as xml:\n{}",
-                record.to_xml_formatted().unwrap()
-            );
+        for record_res in Record::from_binary_file(&bin_file_op.unwrap()).expect("Start Binary File") {
+            match record_res {
+                Ok(record) => println!(
+                    "\nBinary record as xml:\n{}",
+                    record.to_xml_formatted().unwrap()
+                ),
+                Err(e) => eprintln!("Error reading binary record: {e}"),
+            }
         }
     }
 }
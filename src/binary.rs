@@ -1,288 +1,273 @@
-use std::fs::File;
-use std::io::prelude::*;
-use super::Record;
+//! ISO 2709 ("MARC binary") reader and writer.
+//!
+//! See <https://www.loc.gov/marc/specifications/specrecstruc.html> for the
+//! structure this module implements: a 24-byte leader, a directory of
+//! 12-byte entries (one per control/data field), and the variable data
+//! fields themselves, with the whole record terminated by a record
+//! terminator byte.
+
+use super::marc8;
+use super::marc8::Encoding;
 use super::Controlfield;
 use super::Field;
+use super::Record;
 use super::Subfield;
+use std::fs::File;
+use std::io::Read;
+
+const FIELD_TERMINATOR: u8 = 0x1E;
+const RECORD_TERMINATOR: u8 = 0x1D;
+const SUBFIELD_DELIMITER: u8 = 0x1F;
 
-const _END_OF_FIELD: u8 = 30; // '\x1E';
-const END_OF_RECORD: u8 = 29; // '\x1D';
-const RECORD_SIZE_ENTRY: usize = 5;
 const LEADER_SIZE: usize = 24;
-const DATA_OFFSET_START: usize = 12;
-const DATA_OFFSET_SIZE: usize = 5;
-const DIRECTORY_ENTRY_LEN: usize = 12;
-const SUBFIELD_SEPARATOR: &str = "\x1F";
-
-/// Iterates over a binary MARC file and emits MARC Records as they are
-/// pulled  from the file.
-pub struct BinaryRecordIterator {
-    file: File,
+const DIRECTORY_ENTRY_SIZE: usize = 12;
+const DIRECTORY_TAG_SIZE: usize = 3;
+const DIRECTORY_FIELD_LEN_SIZE: usize = 4;
+const DIRECTORY_FIELD_POS_SIZE: usize = 5;
+
+/// Smallest a binary record can be: just a leader with an empty
+/// directory and no fields.
+const MIN_RECORD_SIZE: usize = LEADER_SIZE + 1 + 1; // leader + empty directory terminator + record terminator
+
+/// ISO 2709 record lengths are encoded as 5 ASCII digits, so a record
+/// can never exceed this many bytes.
+const MAX_RECORD_SIZE: usize = 99999;
+
+/// A single directory entry: where to find one field's bytes within
+/// the record's data portion.
+struct DirectoryEntry {
+    tag: String,
+    start: usize,
+    end: usize,
 }
 
-impl Iterator for BinaryRecordIterator {
-    type Item = Record;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut bytes: Vec<u8> = Vec::new();
+fn digits_to_usize(bytes: &[u8], what: &str) -> Result<usize, String> {
+    let s = std::str::from_utf8(bytes)
+        .map_err(|e| format!("Invalid {what} bytes: {bytes:?} {e}"))?;
 
-        loop {
-            // Read bytes from the file until we hit an END_OF_RECORD byte.
-            // Pass the read bytes to the Record binary data parser.
-
-            let mut buf: [u8; 1] = [0];
-            match self.file.read(&mut buf) {
-                Ok(count) => {
-                    if count == 1 {
-                        bytes.push(buf[0]);
-                        if buf[0] == END_OF_RECORD {
-                            break;
-                        }
-                    } else {
-                        break; // EOF
-                    }
-                },
-                Err(e) => {
-                    // Can't really return an Err from an Iterator.
-                    // Log the error and wrap it up.
-                    eprintln!("Error reading file: {:?} {}", self.file, e);
-                    break;
-                }
-            }
-        }
-
-        if bytes.len() > 0 {
-            match Record::from_binary(&bytes) {
-                Ok(r) => {
-                    return Some(r);
-                },
-                Err(e) => {
-                    eprintln!("Error processing bytes: {:?} {}", bytes, e);
-                    return None;
-                }
-            }
-        }
-
-        None
-    }
+    s.parse::<usize>()
+        .map_err(|e| format!("Invalid {what} value '{s}': {e}"))
 }
 
-impl BinaryRecordIterator {
-
-    pub fn new(filename: &str) -> Result<Self, String> {
-
-        let file = match File::open(filename) {
-            Ok(f) => f,
-            Err(e) => {
-                return Err(format!("Cannot read MARC file: {filename} {e}"));
-            }
-        };
-
-        Ok(BinaryRecordIterator { file })
+/// The on-the-wire byte for an indicator: its content, or a blank
+/// space for an unset indicator.
+fn indicator_byte(indicator: &super::Indicator) -> u8 {
+    match &indicator.content {
+        Some(c) => c.as_bytes()[0],
+        None => b' ',
     }
 }
 
-/// bytes => String => usize
-fn bytes_to_usize(bytes: &[u8]) -> Result<usize, String> {
+fn usize_to_digits(value: usize, width: usize, what: &str) -> Result<String, String> {
+    let s = format!("{value:0width$}", width = width);
 
-    match std::str::from_utf8(&bytes) {
-        Ok(bytes_str) => {
-            match bytes_str.parse::<usize>() {
-                Ok(num) => Ok(num),
-                Err(e) => Err(format!(
-                    "Error translating string to usize str={bytes_str} {e}"))
-            }
-        },
-        Err(e) => Err(format!("Error translating bytes to string: {bytes:?} {e}"))
+    if s.len() != width {
+        return Err(format!("{what} value {value} does not fit in {width} digits"));
     }
-}
 
-pub struct DirectoryEntry {
-    tag: String,
-    field_start_idx: usize,
-    field_end_idx: usize,
+    Ok(s)
 }
 
 impl DirectoryEntry {
+    /// Parse the `which`'th (zero-based) 12-byte entry out of the
+    /// directory, translating its field position into an absolute
+    /// byte range within the record.
+    fn parse(which: usize, dir_bytes: &[u8], base_address: usize) -> Result<Self, String> {
+        let start = which * DIRECTORY_ENTRY_SIZE;
+        let entry = &dir_bytes[start..start + DIRECTORY_ENTRY_SIZE];
+
+        let tag = std::str::from_utf8(&entry[0..DIRECTORY_TAG_SIZE])
+            .map_err(|e| format!("Invalid directory tag bytes: {entry:?} {e}"))?
+            .to_string();
+
+        let field_len = digits_to_usize(
+            &entry[DIRECTORY_TAG_SIZE..DIRECTORY_TAG_SIZE + DIRECTORY_FIELD_LEN_SIZE],
+            "directory field length",
+        )?;
+
+        let field_pos = digits_to_usize(
+            &entry[DIRECTORY_TAG_SIZE + DIRECTORY_FIELD_LEN_SIZE..],
+            "directory field position",
+        )?;
+
+        if field_len == 0 {
+            return Err(format!(
+                "Directory entry for field {tag} has a length of 0; every field must at least contain its terminator"
+            ));
+        }
 
-    /// 'which' 12-byte entry out of the directory as a whole, zero-based.
-    pub fn new(which: usize, data_start_idx: usize, dir_bytes: &[u8]) -> Result<Self, String> {
-
-        let start = which * DIRECTORY_ENTRY_LEN;
-        let end = start + DIRECTORY_ENTRY_LEN;
-        let bytes = &dir_bytes[start..end];
-
-        let entry_str = match std::str::from_utf8(bytes) {
-            Ok(s) => s,
-            Err(e) => {
-                return Err(format!("Invalid directory bytes: {:?} {}", bytes, e));
-            }
-        };
-
-        let field_tag = &entry_str[0..3];
-        let field_len_str = &entry_str[3..7];
-        let field_pos_str = &entry_str[7..12];
-
-        let field_len = match field_len_str.parse::<usize>() {
-            Ok(l) => l,
-            Err(e) => {
-                return Err(format!(
-                    "Invalid data length value {} {}", field_len_str, e));
-            }
-        };
-
-        // Where does this field start in the record as a whole
-        let field_start_idx = match field_pos_str.parse::<usize>() {
-            Ok(l) => l,
-            Err(e) => {
-                return Err(format!(
-                    "Invalid data position value {} {}", field_pos_str, e));
-            }
-        };
-
-        let start = field_start_idx + data_start_idx;
-        let last = start + field_len - 1; // Discard END_OF_FIELD char
+        let field_start = base_address + field_pos;
+        let field_end = field_start + field_len;
 
         Ok(DirectoryEntry {
-            tag: field_tag.to_string(),
-            field_start_idx: start,
-            field_end_idx: last
+            tag,
+            start: field_start,
+            end: field_end,
         })
     }
 }
 
 impl Record {
-
-    // Creates a Record from a MARC binary data file.
-    pub fn from_binary_file(filename: &str) -> Result<BinaryRecordIterator, String> {
-        BinaryRecordIterator::new(filename)
+    /// Parses a single ISO 2709 binary record, choosing MARC-8 or
+    /// UTF-8 decoding based on the leader's character coding scheme
+    /// (leader position 9: `'a'` for UTF-8, blank for MARC-8).
+    pub fn from_binary(bytes: &[u8]) -> Result<Record, String> {
+        let encoding = Record::detect_binary_encoding(bytes)?;
+        Record::from_binary_with_encoding(bytes, encoding)
     }
 
-    /// Creates a Rrecord from MARC binary data.
-    //
-    // https://www.loc.gov/marc/bibliographic/bdleader.html
-    // 24-byte leader
-    //   5-byte record length
-    //   other stuff
-    //   5-byte data start index
-    //   other stuff
-    //
-    // https://www.loc.gov/marc/bibliographic/bddirectory.html
-    // 12-byte field directory entries
-    //
-    // Control fields and data fields.
-    pub fn from_binary(bytes: &Vec<u8>) -> Result<Record, String> {
-        let mut record = Record::new();
+    /// Leader position 9 is always plain ASCII, so it can be read
+    /// before we know which encoding the rest of the record is in.
+    fn detect_binary_encoding(bytes: &[u8]) -> Result<Encoding, String> {
+        if bytes.len() < LEADER_SIZE {
+            return Err(format!(
+                "Binary record is too short: {} bytes",
+                bytes.len()
+            ));
+        }
 
-        let rec_bytes = bytes.as_slice();
-        let rec_byte_count = rec_bytes.len();
+        match bytes[9] {
+            b'a' => Ok(Encoding::Utf8),
+            _ => Ok(Encoding::Marc8),
+        }
+    }
 
-        if rec_byte_count < RECORD_SIZE_ENTRY {
-            return Err(format!("Binary record is too short"));
+    /// Parses a single ISO 2709 binary record using an explicit
+    /// character encoding for its field content, rather than
+    /// detecting it from the leader.
+    pub fn from_binary_with_encoding(bytes: &[u8], encoding: Encoding) -> Result<Record, String> {
+        if bytes.len() < MIN_RECORD_SIZE {
+            return Err(format!(
+                "Binary record is too short: {} bytes",
+                bytes.len()
+            ));
         }
 
-        let leader_bytes = &rec_bytes[0..LEADER_SIZE];
+        let leader_bytes = &bytes[0..LEADER_SIZE];
 
-        // Reported size of the record
-        let size_bytes = &leader_bytes[0..RECORD_SIZE_ENTRY];
+        let mut record = Record::new();
+        record.set_leader(
+            std::str::from_utf8(leader_bytes)
+                .map_err(|e| format!("Leader is not valid UTF-8: {e}"))?,
+        )?;
+        let leader = record.leader.as_ref().unwrap();
 
-        // Where in this pile of bytes do the control/data fields tart.
-        let data_offset_bytes =
-            &leader_bytes[DATA_OFFSET_START..(DATA_OFFSET_START + DATA_OFFSET_SIZE)];
+        let rec_len = leader.record_length()?;
 
-        let rec_size = match bytes_to_usize(&size_bytes) {
-            Ok(n) => n,
-            Err(e) => { return Err(e); }
-        };
+        if rec_len != bytes.len() {
+            return Err(format!(
+                "Record length mismatch: leader says {rec_len}, record is {} bytes",
+                bytes.len()
+            ));
+        }
 
-        if rec_byte_count != rec_size {
+        if !(LEADER_SIZE..=MAX_RECORD_SIZE).contains(&rec_len) {
             return Err(format!(
-                "Record has incorrect size reported={} real={}", rec_size, rec_byte_count));
+                "Record length {rec_len} is out of range [{LEADER_SIZE}, {MAX_RECORD_SIZE}]"
+            ));
         }
 
-        record.set_leader_bytes(&leader_bytes)?;
+        let base_address = leader.base_address()?;
 
-        let data_start_idx = match bytes_to_usize(data_offset_bytes) {
-            Ok(n) => n,
-            Err(e) => { return Err(e); }
-        };
+        // Must leave room for at least the directory's own terminator
+        // immediately before it, even for a record with no fields.
+        if base_address < LEADER_SIZE + 1 || base_address >= bytes.len() {
+            return Err(format!("Base address of data {base_address} is out of range"));
+        }
+
+        if bytes[bytes.len() - 1] != RECORD_TERMINATOR {
+            return Err("Record is not terminated by the record terminator".to_string());
+        }
 
-        // -1 to skip the END_OF_FIELD
-        let dir_bytes = &rec_bytes[LEADER_SIZE..(data_start_idx - 1)];
+        // The directory runs from just after the leader to the field
+        // terminator that immediately precedes the base address.
+        let dir_bytes = &bytes[LEADER_SIZE..base_address - 1];
 
-        let dir_len = dir_bytes.len();
-        if dir_len == 0 || dir_len % DIRECTORY_ENTRY_LEN != 0 {
-            return Err(format!("Invalid directory length {}", dir_len));
+        if dir_bytes.is_empty() {
+            return Ok(record);
         }
 
-        let dir_count = dir_bytes.len() / DIRECTORY_ENTRY_LEN;
-        let mut dir_idx = 0;
+        if dir_bytes.len() % DIRECTORY_ENTRY_SIZE != 0 {
+            return Err(format!(
+                "Directory length {} is not a multiple of {DIRECTORY_ENTRY_SIZE}",
+                dir_bytes.len()
+            ));
+        }
 
-        while dir_idx < dir_count {
+        let entry_count = dir_bytes.len() / DIRECTORY_ENTRY_SIZE;
 
-            let dir_entry = DirectoryEntry::new(dir_idx, data_start_idx, &dir_bytes)?;
+        for which in 0..entry_count {
+            let entry = DirectoryEntry::parse(which, dir_bytes, base_address)?;
 
-            if let Err(e) =
-                record.process_directory_entry(&rec_bytes, &dir_entry, rec_byte_count) {
+            if entry.end > bytes.len() - 1 {
                 return Err(format!(
-                    "Error processing directory entry index={} {}", dir_idx, e));
+                    "Field {} extends beyond the end of the record",
+                    entry.tag
+                ));
             }
 
-            dir_idx += 1;
+            record.add_binary_field(&entry, bytes, encoding)?;
         }
 
         Ok(record)
     }
 
-
-    /// Unpack a single control field / data field and append to the
-    /// record in progress.
-    //
-    // https://www.loc.gov/marc/bibliographic/bddirectory.html
-    fn process_directory_entry(
+    /// Unpacks one field's raw bytes (as located by its directory
+    /// entry) and appends it to the record in progress.
+    fn add_binary_field(
         &mut self,
-        rec_bytes: &[u8],
-        dir_entry: &DirectoryEntry,
-        rec_byte_count: usize,
+        entry: &DirectoryEntry,
+        bytes: &[u8],
+        encoding: Encoding,
     ) -> Result<(), String> {
-
-        if (dir_entry.field_end_idx) >= rec_byte_count {
-            return Err(format!(
-                "Field length exceeds length of record for tag={}", dir_entry.tag));
-        }
-
-        let field_bytes = &rec_bytes[dir_entry.field_start_idx..dir_entry.field_end_idx];
-
-        let field_str = match std::str::from_utf8(&field_bytes) {
-            Ok(s) => s,
-            Err(e) => {
-                return Err(format!(
-                    "Field data is not UTF8 compatible: {:?} {}", field_bytes, e));
-            }
+        // The field's terminator is included in its directory length;
+        // strip it before further parsing.
+        let field_bytes = &bytes[entry.start..entry.end - 1];
+
+        let field_str = match encoding {
+            Encoding::Utf8 => std::str::from_utf8(field_bytes)
+                .map(str::to_string)
+                .map_err(|e| format!("Field {} is not valid UTF-8: {e}", entry.tag))?,
+            Encoding::Marc8 => marc8::decode_marc8(field_bytes)
+                .map_err(|e| format!("Field {} is not valid MARC-8: {e}", entry.tag))?,
+            Encoding::Latin1 => marc8::decode_latin1(field_bytes),
         };
+        let field_str = field_str.as_str();
 
-        if dir_entry.tag.as_str() < "010" { // Control field
-            let mut cf = Controlfield::new(&dir_entry.tag)?;
-            if field_str.len() > 0 {
-                cf.set_content(&field_str);
+        if entry.tag.as_str() < "010" {
+            let mut cf = Controlfield::new(&entry.tag)?;
+            if !field_str.is_empty() {
+                cf.set_content(field_str);
             }
             self.control_fields.push(cf);
             return Ok(());
         }
 
-        // 3-bytes for tag
-        // 1 byte for indicator 1
-        // 1 byte for indicator 2
-        let mut field = Field::new(&dir_entry.tag).unwrap(); // tag char count is known good
-        field.set_ind1(&field_str[..1]).unwrap(); // ind char count is known good
-        field.set_ind2(&field_str[1..2]).unwrap(); // ind char count is known good
+        if field_str.len() < 2 {
+            return Err(format!("Data field {} is missing its indicators", entry.tag));
+        }
+
+        if !field_str.is_char_boundary(1) || !field_str.is_char_boundary(2) {
+            return Err(format!("Data field {} has malformed indicators", entry.tag));
+        }
+
+        let mut field = Field::new(&entry.tag)?;
+        field.set_ind1(&field_str[0..1])?;
+        field.set_ind2(&field_str[1..2])?;
+
+        // Skip the leading chunk before the first subfield delimiter;
+        // it's whatever follows the indicators (normally nothing) and
+        // is not a subfield.
+        for part in field_str[2..].split(SUBFIELD_DELIMITER as char).skip(1) {
+            if part.is_empty() {
+                return Err(format!("Field {} has an empty subfield", entry.tag));
+            }
 
-        // Split the remainder on the subfield separator and
-        // build Field's from them.
-        let field_parts: Vec<&str> = field_str.split(SUBFIELD_SEPARATOR).collect();
+            if !part.is_char_boundary(1) {
+                return Err(format!("Field {} has a malformed subfield code", entry.tag));
+            }
 
-        for part in &field_parts[1..] { // skip the initial SUBFIELD_SEPARATOR
-            let mut sf = Subfield::new(&part[..1]).unwrap(); // code size is known good
+            let mut sf = Subfield::new(&part[0..1])?;
             if part.len() > 1 {
                 sf.set_content(&part[1..]);
             }
@@ -294,20 +279,165 @@ impl Record {
         Ok(())
     }
 
+    /// Serializes the record as UTF-8 ISO 2709 binary data. See
+    /// [`Record::to_binary_with_encoding`] to emit MARC-8 instead.
     pub fn to_binary(&self) -> Result<Vec<u8>, String> {
+        self.to_binary_with_encoding(Encoding::Utf8)
+    }
 
-        // It's technically possible for a Record to have no leader.
-        // Build one if necessary.
-        let mut bytes: Vec<u8> = match &self.leader {
-            Some(l) => l.content.as_bytes().to_vec(),
-            None => (0..LEADER_SIZE).map(|_| '0' as u8).collect::<Vec<u8>>()
+    /// Serializes the record as ISO 2709 binary data, recomputing the
+    /// directory and the leader's record-length / base-address fields
+    /// from the actual field content rather than trusting whatever was
+    /// parsed into them, and encoding field content per `encoding`.
+    pub fn to_binary_with_encoding(&self, encoding: Encoding) -> Result<Vec<u8>, String> {
+        let mut directory = Vec::new();
+        let mut data = Vec::new();
+
+        let encode = |content: &str| -> Result<Vec<u8>, String> {
+            match encoding {
+                Encoding::Utf8 => Ok(content.as_bytes().to_vec()),
+                Encoding::Marc8 => marc8::encode_marc8(content),
+                Encoding::Latin1 => marc8::encode_latin1(content),
+            }
         };
 
-        let field_count = self.control_fields.len() + self.fields.len();
+        for cf in &self.control_fields {
+            let start = data.len();
+
+            if let Some(content) = &cf.content {
+                data.extend_from_slice(&encode(content)?);
+            }
+            data.push(FIELD_TERMINATOR);
+
+            self.add_directory_entry(&mut directory, &cf.tag.content, start, data.len() - start)?;
+        }
+
+        for field in &self.fields {
+            let start = data.len();
+
+            data.push(indicator_byte(&field.ind1));
+            data.push(indicator_byte(&field.ind2));
+
+            for sf in &field.subfields {
+                data.push(SUBFIELD_DELIMITER);
+                data.extend_from_slice(sf.code.as_bytes());
+                if let Some(content) = &sf.content {
+                    data.extend_from_slice(&encode(content)?);
+                }
+            }
+
+            data.push(FIELD_TERMINATOR);
+
+            self.add_directory_entry(&mut directory, &field.tag.content, start, data.len() - start)?;
+        }
+
+        directory.push(FIELD_TERMINATOR);
+
+        let base_address = LEADER_SIZE + directory.len();
+        let total_len = base_address + data.len() + 1; // +1 for the record terminator
+
+        if total_len > MAX_RECORD_SIZE {
+            return Err(format!(
+                "Record of {total_len} bytes exceeds the maximum of {MAX_RECORD_SIZE}"
+            ));
+        }
+
+        let mut leader = self.leader.clone().unwrap_or_else(super::Leader::blank);
+        leader.set_record_length(total_len)?;
+        leader.set_base_address(base_address)?;
+        leader.set_character_coding_scheme(match encoding {
+            Encoding::Utf8 => 'a',
+            Encoding::Marc8 | Encoding::Latin1 => ' ',
+        })?;
+
+        let mut bytes = Vec::with_capacity(total_len);
+        bytes.extend_from_slice(leader.content.as_bytes());
+        bytes.extend_from_slice(&directory);
+        bytes.extend_from_slice(&data);
+        bytes.push(RECORD_TERMINATOR);
 
         Ok(bytes)
     }
+
+    fn add_directory_entry(
+        &self,
+        directory: &mut Vec<u8>,
+        tag: &str,
+        start: usize,
+        len: usize,
+    ) -> Result<(), String> {
+        directory.extend_from_slice(tag.as_bytes());
+        directory.extend_from_slice(
+            usize_to_digits(len, DIRECTORY_FIELD_LEN_SIZE, "field length")?.as_bytes(),
+        );
+        directory.extend_from_slice(
+            usize_to_digits(start, DIRECTORY_FIELD_POS_SIZE, "field position")?.as_bytes(),
+        );
+
+        Ok(())
+    }
+
+    /// Returns an iterator that lazily parses each record from a
+    /// binary MARC file, so multi-record `.mrc` files can be
+    /// processed without loading the whole file into memory.
+    pub fn from_binary_file(filename: &str) -> Result<BinaryRecordIterator<File>, String> {
+        let file = File::open(filename)
+            .map_err(|e| format!("Cannot read MARC file: {filename} {e}"))?;
+
+        Ok(BinaryRecordIterator::new(file))
+    }
+}
+
+/// Iterates over a `Read` stream of concatenated ISO 2709 records,
+/// yielding one parsed `Record` at a time. A malformed record yields
+/// an `Err` without aborting the rest of the stream; an I/O error on
+/// the underlying reader is assumed unrecoverable and ends iteration.
+pub struct BinaryRecordIterator<R: Read> {
+    reader: std::io::BufReader<R>,
+    done: bool,
+}
+
+impl<R: Read> BinaryRecordIterator<R> {
+    pub fn new(reader: R) -> Self {
+        BinaryRecordIterator {
+            reader: std::io::BufReader::new(reader),
+            done: false,
+        }
+    }
 }
 
+impl<R: Read> Iterator for BinaryRecordIterator<R> {
+    type Item = Result<Record, String>;
 
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut bytes: Vec<u8> = Vec::new();
+        let mut buf = [0u8; 1];
+
+        loop {
+            match self.reader.read(&mut buf) {
+                Ok(0) => break, // EOF
+                Ok(_) => {
+                    bytes.push(buf[0]);
+                    if buf[0] == RECORD_TERMINATOR {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(format!("Error reading MARC stream: {e}")));
+                }
+            }
+        }
 
+        if bytes.is_empty() {
+            self.done = true;
+            return None;
+        }
+
+        Some(Record::from_binary(&bytes))
+    }
+}
@@ -106,6 +106,10 @@ impl Record {
 
         if len < 3 { return Ok(()); }
 
+        if !line.is_char_boundary(3) {
+            return Err(format!("Malformed breaker line tag: {line}"));
+        }
+
         let tag = &line[..3];
 
         if tag.eq("LDR") {
@@ -128,16 +132,35 @@ impl Record {
         let mut field = Field::new(tag)?;
 
         if len > 4 {
-            field.set_ind1(&line[4..5]);
+            if !line.is_char_boundary(4) || !line.is_char_boundary(5) {
+                return Err(format!("Malformed breaker line indicators: {line}"));
+            }
+            field.set_ind1(breaker_indicator(&line[4..5]))?;
         }
 
         if len > 5 {
-            field.set_ind2(&line[5..6]);
+            if !line.is_char_boundary(5) || !line.is_char_boundary(6) {
+                return Err(format!("Malformed breaker line indicators: {line}"));
+            }
+            field.set_ind2(breaker_indicator(&line[5..6]))?;
         }
 
         if len > 6 {
-            for sf in line[6..].split(MARC_BREAKER_SF_DELIMITER) {
-                if sf.len() == 0 { continue; }
+            if !line.is_char_boundary(6) {
+                return Err(format!("Malformed breaker line subfields: {line}"));
+            }
+
+            // The chunk before the first "$" is whatever follows the
+            // indicators consumed above (normally nothing), not a subfield.
+            for sf in line[6..].split(MARC_BREAKER_SF_DELIMITER).skip(1) {
+                if sf.is_empty() {
+                    return Err(format!("Field {tag} has a zero-length subfield code"));
+                }
+
+                if !sf.is_char_boundary(1) {
+                    return Err(format!("Field {tag} has a malformed subfield code: {sf}"));
+                }
+
                 let mut subfield = Subfield::new(&sf[..1])?;
                 if sf.len() > 1 {
                     subfield.set_content(unescape_from_breaker(&sf[1..]).as_str());
@@ -151,3 +174,14 @@ impl Record {
         Ok(())
     }
 }
+
+/// Decodes the breaker format's `\` sentinel for an unset/blank
+/// indicator back into a plain space, which [`Indicator::new`] maps
+/// to `None`.
+fn breaker_indicator(value: &str) -> &str {
+    if value == "\\" {
+        " "
+    } else {
+        value
+    }
+}
@@ -0,0 +1,175 @@
+//! MARC-in-JSON serialization, using the widely-used layout documented
+//! at <https://github.com/marc4j/marc4j/wiki/MARC-in-JSON-Schema>: a
+//! top-level object with a `leader` string and a `fields` array whose
+//! elements are single-key objects keyed by tag.
+
+use super::Controlfield;
+use super::Field;
+use super::Record;
+use super::Subfield;
+use serde_json::{Map, Value};
+
+impl Record {
+    /// Creates the MARC-in-JSON representation of this record as a
+    /// compact String.
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(&self.to_json_value()).map_err(|e| format!("Error creating JSON: {e}"))
+    }
+
+    /// Same as [`Record::to_json`], but pretty-printed.
+    pub fn to_json_pretty(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(&self.to_json_value())
+            .map_err(|e| format!("Error creating JSON: {e}"))
+    }
+
+    fn to_json_value(&self) -> Value {
+        let mut fields = Vec::new();
+
+        for cf in &self.control_fields {
+            let mut entry = Map::new();
+            entry.insert(
+                cf.tag.content.clone(),
+                Value::String(cf.content.clone().unwrap_or_default()),
+            );
+            fields.push(Value::Object(entry));
+        }
+
+        for field in &self.fields {
+            let mut subfields = Vec::new();
+            for sf in &field.subfields {
+                let mut sf_entry = Map::new();
+                sf_entry.insert(
+                    sf.code.clone(),
+                    Value::String(sf.content.clone().unwrap_or_default()),
+                );
+                subfields.push(Value::Object(sf_entry));
+            }
+
+            let mut field_obj = Map::new();
+            field_obj.insert("ind1".to_string(), Value::String(indicator_json(&field.ind1)));
+            field_obj.insert("ind2".to_string(), Value::String(indicator_json(&field.ind2)));
+            field_obj.insert("subfields".to_string(), Value::Array(subfields));
+
+            let mut entry = Map::new();
+            entry.insert(field.tag.content.clone(), Value::Object(field_obj));
+            fields.push(Value::Object(entry));
+        }
+
+        let mut top = Map::new();
+        top.insert(
+            "leader".to_string(),
+            Value::String(self.leader.as_ref().map(|l| l.content.clone()).unwrap_or_default()),
+        );
+        top.insert("fields".to_string(), Value::Array(fields));
+
+        Value::Object(top)
+    }
+
+    /// Parses the MARC-in-JSON representation of a record.
+    pub fn from_json(json: &str) -> Result<Record, String> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|e| format!("Error parsing JSON: {e}"))?;
+
+        let top = value
+            .as_object()
+            .ok_or_else(|| "MARC-in-JSON value must be an object".to_string())?;
+
+        let mut record = Record::new();
+
+        if let Some(leader) = top.get("leader").and_then(Value::as_str) {
+            if !leader.is_empty() {
+                record.set_leader(leader)?;
+            }
+        }
+
+        let fields = top
+            .get("fields")
+            .and_then(Value::as_array)
+            .ok_or_else(|| "MARC-in-JSON value is missing a \"fields\" array".to_string())?;
+
+        for field_value in fields {
+            let field_obj = field_value
+                .as_object()
+                .ok_or_else(|| "Each field must be an object".to_string())?;
+
+            if field_obj.len() != 1 {
+                return Err(format!(
+                    "Each field must have exactly one tag key, found {}",
+                    field_obj.len()
+                ));
+            }
+
+            let (tag, contents) = field_obj
+                .iter()
+                .next()
+                .ok_or_else(|| "Each field must have exactly one tag key".to_string())?;
+
+            match contents {
+                Value::String(content) => {
+                    let mut cf = Controlfield::new(tag)?;
+                    if !content.is_empty() {
+                        cf.set_content(content);
+                    }
+                    record.control_fields.push(cf);
+                }
+                Value::Object(data) => {
+                    let mut field = Field::new(tag)?;
+
+                    if let Some(ind1) = data.get("ind1").and_then(Value::as_str) {
+                        field.set_ind1(ind1)?;
+                    }
+
+                    if let Some(ind2) = data.get("ind2").and_then(Value::as_str) {
+                        field.set_ind2(ind2)?;
+                    }
+
+                    let subfields = data
+                        .get("subfields")
+                        .and_then(Value::as_array)
+                        .ok_or_else(|| format!("Data field {tag} is missing its subfields array"))?;
+
+                    for sf_value in subfields {
+                        let sf_obj = sf_value
+                            .as_object()
+                            .ok_or_else(|| "Each subfield must be an object".to_string())?;
+
+                        if sf_obj.len() != 1 {
+                            return Err(format!(
+                                "Each subfield must have exactly one code key, found {}",
+                                sf_obj.len()
+                            ));
+                        }
+
+                        let (code, content) = sf_obj
+                            .iter()
+                            .next()
+                            .ok_or_else(|| "Each subfield must have exactly one code key".to_string())?;
+
+                        let mut sf = Subfield::new(code)?;
+                        let content = content
+                            .as_str()
+                            .ok_or_else(|| format!("Subfield {code} content must be a string"))?;
+                        if !content.is_empty() {
+                            sf.set_content(content);
+                        }
+
+                        field.subfields.push(sf);
+                    }
+
+                    record.fields.push(field);
+                }
+                _ => {
+                    return Err(format!("Field {tag} must be a string or an object"));
+                }
+            }
+        }
+
+        Ok(record)
+    }
+}
+
+/// A blank indicator is conventionally represented as a single space
+/// in MARC-in-JSON.
+fn indicator_json(indicator: &super::Indicator) -> String {
+    indicator.content.clone().unwrap_or_else(|| " ".to_string())
+}
@@ -0,0 +1,178 @@
+//! Typed accessors for the fixed, positionally-defined fields of the
+//! MARC leader. See <https://www.loc.gov/marc/bibliographic/bdleader.html>.
+
+use super::Leader;
+use super::Record;
+
+const LEADER_SIZE: usize = 24;
+const RECORD_LENGTH: std::ops::Range<usize> = 0..5;
+const RECORD_STATUS: usize = 5;
+const TYPE_OF_RECORD: usize = 6;
+const BIBLIOGRAPHIC_LEVEL: usize = 7;
+const CHARACTER_CODING_SCHEME: usize = 9;
+const INDICATOR_COUNT: usize = 10;
+const SUBFIELD_CODE_COUNT: usize = 11;
+const BASE_ADDRESS: std::ops::Range<usize> = 12..17;
+const ENTRY_MAP: std::ops::Range<usize> = 20..24;
+
+/// The standard, and by far most common, indicator count.
+const STANDARD_INDICATOR_COUNT: char = '2';
+/// The standard, and by far most common, subfield code count.
+const STANDARD_SUBFIELD_CODE_COUNT: char = '2';
+/// The standard entry map: length-of-length-of-field (4), starting
+/// character position (5), length of implementation-defined portion
+/// (0), undefined (0).
+const STANDARD_ENTRY_MAP: &str = "4500";
+
+fn digits_at(content: &str, range: std::ops::Range<usize>, what: &str) -> Result<usize, String> {
+    content[range]
+        .parse::<usize>()
+        .map_err(|e| format!("Invalid {what} in leader '{content}': {e}"))
+}
+
+fn set_digits_at(
+    content: &mut String,
+    range: std::ops::Range<usize>,
+    value: usize,
+    what: &str,
+) -> Result<(), String> {
+    let width = range.len();
+    let digits = format!("{value:0width$}", width = width);
+
+    if digits.len() != width {
+        return Err(format!("{what} value {value} does not fit in {width} digits"));
+    }
+
+    content.replace_range(range, &digits);
+
+    Ok(())
+}
+
+fn char_at(content: &str, pos: usize) -> char {
+    content.as_bytes()[pos] as char
+}
+
+fn set_char_at(content: &mut String, pos: usize, value: char) -> Result<(), String> {
+    if !value.is_ascii() {
+        return Err(format!("Leader value '{value}' is not ASCII"));
+    }
+
+    content.replace_range(pos..pos + 1, &value.to_string());
+
+    Ok(())
+}
+
+impl Leader {
+    /// A blank leader: 24 spaces. Callers typically follow this up
+    /// with [`Record::repair_leader`] and/or explicit field setters.
+    pub fn blank() -> Self {
+        Leader {
+            content: " ".repeat(LEADER_SIZE),
+        }
+    }
+
+    pub fn record_length(&self) -> Result<usize, String> {
+        digits_at(&self.content, RECORD_LENGTH, "record length")
+    }
+
+    pub fn set_record_length(&mut self, value: usize) -> Result<(), String> {
+        set_digits_at(&mut self.content, RECORD_LENGTH, value, "record length")
+    }
+
+    pub fn record_status(&self) -> char {
+        char_at(&self.content, RECORD_STATUS)
+    }
+
+    pub fn set_record_status(&mut self, value: char) -> Result<(), String> {
+        set_char_at(&mut self.content, RECORD_STATUS, value)
+    }
+
+    pub fn type_of_record(&self) -> char {
+        char_at(&self.content, TYPE_OF_RECORD)
+    }
+
+    pub fn set_type_of_record(&mut self, value: char) -> Result<(), String> {
+        set_char_at(&mut self.content, TYPE_OF_RECORD, value)
+    }
+
+    pub fn bibliographic_level(&self) -> char {
+        char_at(&self.content, BIBLIOGRAPHIC_LEVEL)
+    }
+
+    pub fn set_bibliographic_level(&mut self, value: char) -> Result<(), String> {
+        set_char_at(&mut self.content, BIBLIOGRAPHIC_LEVEL, value)
+    }
+
+    /// `'a'` for Unicode/UTF-8 content, blank for MARC-8.
+    pub fn character_coding_scheme(&self) -> char {
+        char_at(&self.content, CHARACTER_CODING_SCHEME)
+    }
+
+    pub fn set_character_coding_scheme(&mut self, value: char) -> Result<(), String> {
+        set_char_at(&mut self.content, CHARACTER_CODING_SCHEME, value)
+    }
+
+    pub fn indicator_count(&self) -> char {
+        char_at(&self.content, INDICATOR_COUNT)
+    }
+
+    pub fn set_indicator_count(&mut self, value: char) -> Result<(), String> {
+        set_char_at(&mut self.content, INDICATOR_COUNT, value)
+    }
+
+    pub fn subfield_code_count(&self) -> char {
+        char_at(&self.content, SUBFIELD_CODE_COUNT)
+    }
+
+    pub fn set_subfield_code_count(&mut self, value: char) -> Result<(), String> {
+        set_char_at(&mut self.content, SUBFIELD_CODE_COUNT, value)
+    }
+
+    pub fn base_address(&self) -> Result<usize, String> {
+        digits_at(&self.content, BASE_ADDRESS, "base address of data")
+    }
+
+    pub fn set_base_address(&mut self, value: usize) -> Result<(), String> {
+        set_digits_at(&mut self.content, BASE_ADDRESS, value, "base address of data")
+    }
+
+    pub fn entry_map(&self) -> &str {
+        &self.content[ENTRY_MAP]
+    }
+
+    pub fn set_entry_map(&mut self, value: &str) -> Result<(), String> {
+        if value.len() != ENTRY_MAP.len() {
+            return Err(format!("Entry map '{value}' must be {} bytes", ENTRY_MAP.len()));
+        }
+
+        self.content.replace_range(ENTRY_MAP, value);
+
+        Ok(())
+    }
+}
+
+impl Record {
+    /// Normalizes the leader's indicator count, subfield code count,
+    /// and entry map to their standard MARC21 values, creating a
+    /// blank leader first if the record doesn't have one yet. This
+    /// does not touch the record length or base address of data,
+    /// which [`Record::to_binary`] recomputes from the actual field
+    /// content on every call.
+    pub fn repair_leader(&mut self) {
+        if self.leader.is_none() {
+            self.leader = Some(Leader::blank());
+        }
+
+        let leader = self.leader.as_mut().unwrap();
+
+        leader
+            .set_indicator_count(STANDARD_INDICATOR_COUNT)
+            .expect("Standard indicator count is always ASCII");
+        leader
+            .set_subfield_code_count(STANDARD_SUBFIELD_CODE_COUNT)
+            .expect("Standard subfield code count is always ASCII");
+        leader
+            .set_entry_map(STANDARD_ENTRY_MAP)
+            .expect("Standard entry map is always the correct width");
+    }
+}
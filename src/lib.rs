@@ -6,6 +6,11 @@ pub use self::record::Record;
 pub use self::record::Subfield;
 pub use self::record::Tag;
 
+pub mod binary;
 pub mod breaker;
+pub mod json;
+pub mod leader;
+pub mod marc8;
+pub mod query;
 pub mod record;
 pub mod xml;
@@ -0,0 +1,270 @@
+//! MARC-8 ⇆ UTF-8 transcoding.
+//!
+//! MARC-8 is the legacy multi-byte character encoding used by most
+//! binary MARC records in the wild, signaled by leader position 9
+//! being blank (as opposed to `'a'` for UTF-8). It designates working
+//! character sets into G0/G1 via `0x1B` escape sequences and, for
+//! accented Latin characters, encodes the diacritic *before* its base
+//! letter - the opposite of Unicode's combining-mark order. See
+//! <https://www.loc.gov/marc/specifications/speccharmarc8.html>.
+//!
+//! This module implements the common case: the default Basic Latin
+//! (ASCII) G0 set, the Extended Latin (ANSEL) G1 set used for
+//! accented characters, and the escape sequences that (re)select
+//! them.
+
+/// Which character encoding a binary record's fields are in,
+/// typically chosen based on leader position 9.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Leader position 9 == 'a'
+    Utf8,
+    /// Leader position 9 == ' ' (blank)
+    Marc8,
+    /// Not a standard leader position 9 value; some older ILS exports
+    /// mislabel Latin-1 ("ANSI") content as blank/MARC-8 and must be
+    /// requested explicitly rather than detected.
+    Latin1,
+}
+
+/// Decodes Latin-1 (ISO 8859-1) bytes into a UTF-8 `String`. Latin-1
+/// is a single-byte encoding whose codepoints match Unicode's first
+/// 256 codepoints one for one, so this is a plain byte-to-char map
+/// with no escape sequences or reordering involved.
+pub fn decode_latin1(bytes: &[u8]) -> String {
+    encoding_rs::mem::decode_latin1(bytes).into_owned()
+}
+
+/// Encodes a UTF-8 `str` as Latin-1 bytes, failing if it contains any
+/// character outside Latin-1's range.
+pub fn encode_latin1(value: &str) -> Result<Vec<u8>, String> {
+    if let Some(c) = value.chars().find(|c| (*c as u32) > 0xFF) {
+        return Err(format!("Character '{c}' is not representable in Latin-1"));
+    }
+
+    Ok(encoding_rs::mem::encode_latin1_lossy(value).into_owned())
+}
+
+use unicode_normalization::UnicodeNormalization;
+
+const ESCAPE: u8 = 0x1B;
+
+/// ESC ( B - designate G0 as Basic Latin (ASCII).
+const G0_ASCII: [u8; 2] = [0x28, 0x42];
+/// ESC ) E - designate G1 as the ANSEL Extended Latin set.
+const G1_EXTENDED_LATIN_E: [u8; 2] = [0x29, 0x45];
+/// ESC ) 1 - same as above; some exports use the numeric form.
+const G1_EXTENDED_LATIN_1: [u8; 2] = [0x29, 0x31];
+
+/// Maps an ANSEL diacritic byte (G1, 0xA0-0xFF) to the Unicode
+/// combining mark that follows its base letter.
+fn ansel_combining_mark(byte: u8) -> Option<char> {
+    Some(match byte {
+        0xE0 => '\u{0309}', // candrabindu -> hook above (approximation)
+        0xE1 => '\u{0300}', // grave
+        0xE2 => '\u{0301}', // acute
+        0xE3 => '\u{0302}', // circumflex
+        0xE4 => '\u{0303}', // tilde
+        0xE5 => '\u{0304}', // macron
+        0xE6 => '\u{0306}', // breve
+        0xE7 => '\u{0307}', // dot above
+        0xE8 => '\u{0308}', // dieresis/umlaut
+        0xE9 => '\u{030C}', // caron
+        0xEA => '\u{030A}', // ring above
+        0xEB => '\u{FE20}', // ligature left half
+        0xEC => '\u{FE21}', // ligature right half
+        0xED => '\u{0323}', // dot below
+        0xEE => '\u{0324}', // double dot below
+        0xEF => '\u{0325}', // ring below
+        0xF0 => '\u{0327}', // cedilla
+        0xF1 => '\u{0328}', // ogonek
+        0xF2 => '\u{0332}', // underscore
+        0xF9 => '\u{0333}', // double underscore
+        _ => return None,
+    })
+}
+
+/// Maps a plain (non-diacritic) ANSEL byte to its Unicode codepoint.
+fn ansel_letter(byte: u8) -> Option<char> {
+    Some(match byte {
+        0xA1 => '\u{0141}', // uppercase L with stroke
+        0xA2 => '\u{00D8}', // uppercase O with stroke
+        0xA3 => '\u{0110}', // uppercase D with stroke
+        0xA4 => '\u{00DE}', // uppercase thorn
+        0xA5 => '\u{00C6}', // uppercase AE
+        0xA6 => '\u{0152}', // uppercase OE
+        0xB1 => '\u{0142}', // lowercase l with stroke
+        0xB2 => '\u{00F8}', // lowercase o with stroke
+        0xB3 => '\u{0111}', // lowercase d with stroke
+        0xB4 => '\u{00FE}', // lowercase thorn
+        0xB5 => '\u{00E6}', // lowercase ae
+        0xB6 => '\u{0153}', // lowercase oe
+        _ => return None,
+    })
+}
+
+/// Decodes MARC-8 bytes into a UTF-8 `String`, reordering each
+/// ANSEL combining diacritic to follow (rather than precede) its
+/// base character and normalizing the result to NFC.
+pub fn decode_marc8(bytes: &[u8]) -> Result<String, String> {
+    let mut decoded = String::new();
+    let mut pending_marks: Vec<char> = Vec::new();
+    let mut in_g1 = false;
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+
+        if b == ESCAPE {
+            if bytes[i + 1..].starts_with(&G0_ASCII) {
+                in_g1 = false;
+                i += 1 + G0_ASCII.len();
+                continue;
+            } else if bytes[i + 1..].starts_with(&G1_EXTENDED_LATIN_E)
+                || bytes[i + 1..].starts_with(&G1_EXTENDED_LATIN_1)
+            {
+                in_g1 = true;
+                i += 1 + G1_EXTENDED_LATIN_E.len();
+                continue;
+            } else {
+                return Err(format!(
+                    "Unrecognized MARC-8 escape sequence at byte {i}: {:?}",
+                    &bytes[i..(i + 3).min(bytes.len())]
+                ));
+            }
+        }
+
+        if in_g1 {
+            if let Some(mark) = ansel_combining_mark(b) {
+                pending_marks.push(mark);
+                i += 1;
+                continue;
+            }
+
+            if let Some(c) = ansel_letter(b) {
+                decoded.push(c);
+                decoded.extend(pending_marks.drain(..));
+                i += 1;
+                continue;
+            }
+        }
+
+        if b < 0x80 {
+            decoded.push(b as char);
+            decoded.extend(pending_marks.drain(..));
+            i += 1;
+            continue;
+        }
+
+        return Err(format!("Undecodable MARC-8 byte 0x{b:02X} at position {i}"));
+    }
+
+    if !pending_marks.is_empty() {
+        return Err("MARC-8 data ends with an unattached combining diacritic".to_string());
+    }
+
+    Ok(decoded.nfc().collect())
+}
+
+/// Encodes a UTF-8 `str` as MARC-8 bytes, splitting any NFD-decomposed
+/// accented Latin character this module recognizes into its ANSEL
+/// base letter plus diacritic byte(s), reordering each diacritic to
+/// *precede* its base letter (the opposite of Unicode's combining
+/// order), and emitting the escape sequences needed to select the
+/// ANSEL G1 set around those runs. Errors rather than silently
+/// emitting bytes that wouldn't round-trip back through
+/// [`decode_marc8`] if the content contains a character outside plain
+/// ASCII and the ANSEL sets this module recognizes (e.g. CJK, Greek,
+/// Cyrillic).
+pub fn encode_marc8(value: &str) -> Result<Vec<u8>, String> {
+    let chars: Vec<char> = value.nfd().collect();
+    let mut bytes = Vec::new();
+    let mut in_g1 = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let base = chars[i];
+        i += 1;
+
+        let mut marks = Vec::new();
+        while i < chars.len() {
+            match combining_mark_to_ansel(chars[i]) {
+                Some(mark_byte) => {
+                    marks.push(mark_byte);
+                    i += 1;
+                }
+                None => break,
+            }
+        }
+
+        let want_g1 = !marks.is_empty() || letter_to_ansel(base).is_some();
+
+        if want_g1 && !in_g1 {
+            bytes.extend_from_slice(&[ESCAPE, G1_EXTENDED_LATIN_E[0], G1_EXTENDED_LATIN_E[1]]);
+            in_g1 = true;
+        } else if !want_g1 && in_g1 {
+            bytes.extend_from_slice(&[ESCAPE, G0_ASCII[0], G0_ASCII[1]]);
+            in_g1 = false;
+        }
+
+        // MARC-8 places the diacritic(s) before the base letter.
+        bytes.extend_from_slice(&marks);
+
+        if let Some(letter_byte) = letter_to_ansel(base) {
+            bytes.push(letter_byte);
+        } else if base.is_ascii() {
+            bytes.push(base as u8);
+        } else {
+            return Err(format!(
+                "Character '{base}' is not representable in the MARC-8 character sets this module supports"
+            ));
+        }
+    }
+
+    if in_g1 {
+        bytes.extend_from_slice(&[ESCAPE, G0_ASCII[0], G0_ASCII[1]]);
+    }
+
+    Ok(bytes)
+}
+
+fn combining_mark_to_ansel(c: char) -> Option<u8> {
+    Some(match c {
+        '\u{0300}' => 0xE1,
+        '\u{0301}' => 0xE2,
+        '\u{0302}' => 0xE3,
+        '\u{0303}' => 0xE4,
+        '\u{0304}' => 0xE5,
+        '\u{0306}' => 0xE6,
+        '\u{0307}' => 0xE7,
+        '\u{0308}' => 0xE8,
+        '\u{030C}' => 0xE9,
+        '\u{030A}' => 0xEA,
+        '\u{0323}' => 0xED,
+        '\u{0324}' => 0xEE,
+        '\u{0325}' => 0xEF,
+        '\u{0327}' => 0xF0,
+        '\u{0328}' => 0xF1,
+        '\u{0332}' => 0xF2,
+        '\u{0333}' => 0xF9,
+        _ => return None,
+    })
+}
+
+fn letter_to_ansel(c: char) -> Option<u8> {
+    Some(match c {
+        '\u{0141}' => 0xA1,
+        '\u{00D8}' => 0xA2,
+        '\u{0110}' => 0xA3,
+        '\u{00DE}' => 0xA4,
+        '\u{00C6}' => 0xA5,
+        '\u{0152}' => 0xA6,
+        '\u{0142}' => 0xB1,
+        '\u{00F8}' => 0xB2,
+        '\u{0111}' => 0xB3,
+        '\u{00FE}' => 0xB4,
+        '\u{00E6}' => 0xB5,
+        '\u{0153}' => 0xB6,
+        _ => return None,
+    })
+}
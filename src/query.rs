@@ -0,0 +1,213 @@
+//! A compact selector syntax for addressing fields and subfields
+//! without having to chain `get_fields`/`get_subfields` calls by hand.
+//!
+//! A spec is a 3-byte tag, optionally followed by a space and two
+//! indicator constraints, optionally followed by one or more `$code`
+//! subfield selectors:
+//!
+//! - `245$a` - subfield `a` of the `245` field
+//! - `245` - the whole `245` field
+//! - `245$a$b` - subfields `a` and `b`, in that order
+//! - `008` - the `008` control field
+//! - `650 _0$a` - subfield `a`, but only on `650` fields whose first
+//!   indicator is blank and whose second indicator is `0`
+//! - `245 1#$a` - first indicator must be `1`; second indicator is
+//!   unconstrained
+//!
+//! In the indicator position, `_` means "blank" and `#` means "any".
+
+use super::Controlfield;
+use super::Field;
+use super::Record;
+use super::Subfield;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum IndConstraint {
+    Any,
+    Exact(char),
+}
+
+impl IndConstraint {
+    fn parse(c: char) -> Self {
+        match c {
+            '#' => IndConstraint::Any,
+            '_' => IndConstraint::Exact(' '),
+            other => IndConstraint::Exact(other),
+        }
+    }
+
+    fn matches(&self, indicator: &super::Indicator) -> bool {
+        match self {
+            IndConstraint::Any => true,
+            IndConstraint::Exact(c) => {
+                indicator.content.as_deref().unwrap_or(" ") == c.to_string()
+            }
+        }
+    }
+}
+
+struct FieldSpec {
+    tag: String,
+    ind1: IndConstraint,
+    ind2: IndConstraint,
+    subfields: Vec<char>,
+}
+
+impl FieldSpec {
+    fn parse(spec: &str) -> Result<Self, String> {
+        if spec.len() < 3 {
+            return Err(format!("Invalid field spec: '{spec}'"));
+        }
+
+        let tag = &spec[..3];
+        super::Tag::new(tag)?;
+
+        let mut rest = &spec[3..];
+        let mut ind1 = IndConstraint::Any;
+        let mut ind2 = IndConstraint::Any;
+
+        if let Some(stripped) = rest.strip_prefix(' ') {
+            let mut chars = stripped.chars();
+            ind1 = IndConstraint::parse(
+                chars
+                    .next()
+                    .ok_or_else(|| format!("Invalid field spec: '{spec}'"))?,
+            );
+            ind2 = IndConstraint::parse(
+                chars
+                    .next()
+                    .ok_or_else(|| format!("Invalid field spec: '{spec}'"))?,
+            );
+            rest = &stripped[2..];
+        }
+
+        let mut subfields = Vec::new();
+
+        if !rest.is_empty() {
+            if !rest.starts_with('$') {
+                return Err(format!("Invalid field spec: '{spec}'"));
+            }
+
+            for part in rest.split('$').skip(1) {
+                let mut chars = part.chars();
+                let code = chars
+                    .next()
+                    .ok_or_else(|| format!("Invalid subfield code in spec: '{spec}'"))?;
+
+                if chars.next().is_some() {
+                    return Err(format!("Invalid subfield code in spec: '{spec}'"));
+                }
+
+                subfields.push(code);
+            }
+        }
+
+        Ok(FieldSpec {
+            tag: tag.to_string(),
+            ind1,
+            ind2,
+            subfields,
+        })
+    }
+
+    fn matches(&self, field: &Field) -> bool {
+        field.tag.content == self.tag
+            && self.ind1.matches(&field.ind1)
+            && self.ind2.matches(&field.ind2)
+    }
+}
+
+/// The result of a [`Record::query`] call: either the matched control
+/// field, the matched data fields, or - when the spec names one or
+/// more subfield codes - the matched subfields.
+pub enum QueryMatch<'a> {
+    Control(&'a Controlfield),
+    Fields(Vec<&'a Field>),
+    Subfields(Vec<&'a Subfield>),
+}
+
+/// The mutable counterpart to [`QueryMatch`].
+pub enum QueryMatchMut<'a> {
+    Control(&'a mut Controlfield),
+    Fields(Vec<&'a mut Field>),
+    Subfields(Vec<&'a mut Subfield>),
+}
+
+impl Record {
+    /// Resolves a compact field-spec string (see the [module-level
+    /// docs](self)) against this record's fields and subfields.
+    pub fn query(&self, spec: &str) -> Result<QueryMatch<'_>, String> {
+        let fs = FieldSpec::parse(spec)?;
+
+        if fs.tag.as_str() < "010" {
+            return self
+                .control_fields
+                .iter()
+                .find(|cf| cf.tag.content == fs.tag)
+                .map(QueryMatch::Control)
+                .ok_or_else(|| format!("No control field found for tag {}", fs.tag));
+        }
+
+        let fields: Vec<&Field> = self.fields.iter().filter(|f| fs.matches(f)).collect();
+
+        if fs.subfields.is_empty() {
+            return Ok(QueryMatch::Fields(fields));
+        }
+
+        let mut subfields = Vec::new();
+        for field in fields {
+            for code in &fs.subfields {
+                subfields.extend(field.get_subfields(&code.to_string()));
+            }
+        }
+
+        Ok(QueryMatch::Subfields(subfields))
+    }
+
+    /// The mutable counterpart to [`Record::query`], allowing matched
+    /// subfield or field content to be edited in place.
+    pub fn query_mut(&mut self, spec: &str) -> Result<QueryMatchMut<'_>, String> {
+        let fs = FieldSpec::parse(spec)?;
+
+        if fs.tag.as_str() < "010" {
+            return self
+                .control_fields
+                .iter_mut()
+                .find(|cf| cf.tag.content == fs.tag)
+                .map(QueryMatchMut::Control)
+                .ok_or_else(|| format!("No control field found for tag {}", fs.tag));
+        }
+
+        let fields: Vec<&mut Field> = self
+            .fields
+            .iter_mut()
+            .filter(|f| fs.matches(f))
+            .collect();
+
+        if fs.subfields.is_empty() {
+            return Ok(QueryMatchMut::Fields(fields));
+        }
+
+        let mut subfields = Vec::new();
+        for field in fields {
+            let mut matched: Vec<&mut Subfield> = field
+                .subfields
+                .iter_mut()
+                .filter(|sf| fs.subfields.iter().any(|c| sf.code == c.to_string()))
+                .collect();
+
+            // Keep the same code order as the spec (and `query`'s
+            // matching behavior), not physical storage order.
+            matched.sort_by_key(|sf| {
+                fs.subfields
+                    .iter()
+                    .position(|c| sf.code == c.to_string())
+                    .unwrap()
+            });
+
+            subfields.extend(matched);
+        }
+
+        Ok(QueryMatchMut::Subfields(subfields))
+    }
+}
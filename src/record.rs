@@ -1,10 +1,12 @@
+use serde::{Deserialize, Serialize};
+
 const TAG_SIZE: usize = 3;
 const LEADER_SIZE: usize = 24;
 const INDICATOR_SIZE: usize = 1;
 const SF_CODE_SIZE: usize = 1;
 
 /// A single 3-byte tag.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tag {
     pub content: String,
 }
@@ -22,7 +24,7 @@ impl Tag {
 }
 
 /// MARC Control Field whose tag value is < "010"
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Controlfield {
     pub tag: Tag,
     pub content: Option<String>,
@@ -42,7 +44,7 @@ impl Controlfield {
 }
 
 /// A single subfield code + value pair
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Subfield {
     pub code: String,
     pub content: Option<String>,
@@ -66,7 +68,7 @@ impl Subfield {
 }
 
 /// A single 1-byte indicator value
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Indicator {
     pub content: Option<String>,
 }
@@ -88,10 +90,16 @@ impl Indicator {
             })
         }
     }
+
+    /// The indicator's on-the-wire value: its content, or a blank
+    /// space if unset.
+    pub fn as_str(&self) -> &str {
+        self.content.as_deref().unwrap_or(" ")
+    }
 }
 
 /// A MARC Data Field with tag, indicators, and subfields.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Field {
     pub tag: Tag,
     pub ind1: Indicator,
@@ -130,27 +138,54 @@ impl Field {
     pub fn get_subfields(&self, code: &str) -> Vec<&Subfield> {
         self.subfields.iter().filter(|f| f.code.eq(code)).collect()
     }
+
+    pub fn get_subfields_mut(&mut self, code: &str) -> Vec<&mut Subfield> {
+        self.subfields
+            .iter_mut()
+            .filter(|f| f.code.eq(code))
+            .collect()
+    }
+
+    /// Appends a new subfield with the given code and content.
+    pub fn add_subfield(&mut self, code: &str, content: &str) -> Result<(), String> {
+        let mut sf = Subfield::new(code)?;
+        sf.set_content(content);
+        self.subfields.push(sf);
+        Ok(())
+    }
+
+    /// Returns the first subfield with the given code, if any.
+    pub fn first_subfield(&self, code: &str) -> Option<&Subfield> {
+        self.subfields.iter().find(|f| f.code.eq(code))
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Leader {
     pub content: String,
 }
 
 impl Leader {
-    /// Returns Err() if leader does not contain the expected number of bytes
+    /// Returns Err() if leader does not contain the expected number of
+    /// bytes, or contains non-ASCII characters. The leader's typed
+    /// accessors index into it by fixed byte position, which only
+    /// lines up with character boundaries if the content is ASCII.
     pub fn new(content: &str) -> Result<Self, String> {
         if content.bytes().len() != LEADER_SIZE {
             return Err(format!("Invalid leader: {content}"));
         }
 
+        if !content.is_ascii() {
+            return Err(format!("Leader must be ASCII: {content}"));
+        }
+
         Ok(Leader {
             content: String::from(content),
         })
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Record {
     pub leader: Option<Leader>,
     pub control_fields: Vec<Controlfield>,
@@ -190,6 +225,49 @@ impl Record {
             .collect()
     }
 
+    pub fn get_fields_mut(&mut self, tag: &str) -> Vec<&mut Field> {
+        self.fields
+            .iter_mut()
+            .filter(|f| f.tag.content.eq(tag))
+            .collect()
+    }
+
+    /// Returns the first data field with the given tag, if any.
+    pub fn get_field_mut(&mut self, tag: &str) -> Option<&mut Field> {
+        self.fields.iter_mut().find(|f| f.tag.content.eq(tag))
+    }
+
+    /// Returns the data fields whose tag matches `pattern`, a 3-byte
+    /// tag pattern in which `X`/`x` and `?` are wildcards, e.g.
+    /// `"6XX"` or `"65?"` to match the common subject-heading range.
+    pub fn get_fields_matching(&self, pattern: &str) -> Result<Vec<&Field>, String> {
+        if pattern.bytes().len() != TAG_SIZE {
+            return Err(format!("Invalid tag pattern: {pattern}"));
+        }
+
+        Ok(self
+            .fields
+            .iter()
+            .filter(|f| tag_matches_pattern(&f.tag.content, pattern))
+            .collect())
+    }
+
+    /// Inserts a new data field, keeping `fields` sorted by tag.
+    pub fn add_field(&mut self, field: Field) {
+        let pos = self
+            .fields
+            .iter()
+            .position(|f| f.tag.content > field.tag.content)
+            .unwrap_or(self.fields.len());
+        self.fields.insert(pos, field);
+    }
+
+    /// Removes and returns the first data field with the given tag, if any.
+    pub fn remove_field(&mut self, tag: &str) -> Option<Field> {
+        let pos = self.fields.iter().position(|f| f.tag.content.eq(tag))?;
+        Some(self.fields.remove(pos))
+    }
+
     pub fn get_values(&self, tag: &str, sfcode: &str) -> Vec<&str> {
         let mut vec = Vec::new();
         for field in self.get_fields(tag) {
@@ -201,4 +279,45 @@ impl Record {
         }
         vec
     }
+
+    /// Appends a new control field with the given tag and content.
+    pub fn add_control_field(&mut self, tag: &str, content: &str) -> Result<(), String> {
+        let mut cf = Controlfield::new(tag)?;
+        cf.set_content(content);
+        self.control_fields.push(cf);
+        Ok(())
+    }
+
+    /// Builds a new data field from a flat list of alternating
+    /// subfield code/content pairs, e.g.
+    /// `record.add_data_field("650", vec!["a", "Hobbits", "b", "Fiction"])`,
+    /// and inserts it via [`Record::add_field`], keeping `fields`
+    /// sorted by tag.
+    pub fn add_data_field(&mut self, tag: &str, subfield_pairs: Vec<&str>) -> Result<(), String> {
+        if subfield_pairs.len() % 2 != 0 {
+            return Err(format!(
+                "add_data_field for tag {tag} requires an even number of code/content values"
+            ));
+        }
+
+        let mut field = Field::new(tag)?;
+
+        for pair in subfield_pairs.chunks(2) {
+            field.add_subfield(pair[0], pair[1])?;
+        }
+
+        self.add_field(field);
+
+        Ok(())
+    }
+}
+
+/// Matches a 3-byte tag against a pattern of the same length in which
+/// `X`/`x` and `?` stand for "any digit in this position".
+fn tag_matches_pattern(tag: &str, pattern: &str) -> bool {
+    tag.len() == pattern.len()
+        && tag.chars().zip(pattern.chars()).all(|(t, p)| match p {
+            'X' | 'x' | '?' => t.is_ascii_digit(),
+            p => t == p,
+        })
 }
@@ -1,4 +1,6 @@
 use std::fs::File;
+use std::io::BufReader;
+use std::io::Read;
 use xml::reader::{EventReader, XmlEvent};
 
 use super::Controlfield;
@@ -53,118 +55,92 @@ struct XmlParseContext {
     record_complete: bool,
 }
 
-pub struct XmlRecordIterator {
-    reader: Option<EventReader<File>>,
-    string: Option<String>,
-}
-
-impl Iterator for XmlRecordIterator {
-    type Item = Record;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut context = XmlParseContext {
+impl XmlParseContext {
+    fn new() -> Self {
+        XmlParseContext {
             in_cfield: false,
             in_subfield: false,
             in_leader: false,
             record_complete: false,
-        };
-
-        if self.reader.is_some() {
-            self.read_next_from_file(&mut context)
-        } else {
-            self.read_next_from_string(&mut context)
         }
     }
 }
 
-impl XmlRecordIterator {
-    pub fn from_file(filename: &str) -> Result<Self, String> {
-        let file = match File::open(filename) {
-            Ok(f) => f,
-            Err(e) => {
-                return Err(format!("Cannot read MARCXML file: {filename} {e}"));
-            }
-        };
+/// Lazily parses records out of a `<collection>` of MARCXML
+/// `<record>` elements (or a lone `<record>`), yielding one `Record`
+/// each time a `</record>` end-element is seen, so multi-gigabyte
+/// collections can be processed with bounded memory.
+pub struct XmlRecordIterator<R: Read> {
+    reader: EventReader<BufReader<R>>,
+}
 
-        Ok(XmlRecordIterator {
-            string: None,
-            reader: Some(EventReader::new(file)),
-        })
+impl<R: Read> XmlRecordIterator<R> {
+    pub fn new(reader: R) -> Self {
+        XmlRecordIterator {
+            reader: EventReader::new(BufReader::new(reader)),
+        }
     }
 
-    pub fn from_string(xml: &str) -> Result<Self, String> {
-        Ok(XmlRecordIterator {
-            string: Some(xml.to_string()),
-            reader: None,
-        })
+    fn skip_to_end_of_record(&mut self) {
+        loop {
+            match self.reader.next() {
+                Ok(XmlEvent::EndElement { name, .. }) if name.local_name == "record" => return,
+                Ok(XmlEvent::EndDocument) | Err(_) => return,
+                Ok(_) => continue,
+            }
+        }
     }
+}
 
-    fn read_next_from_string(&mut self, context: &mut XmlParseContext) -> Option<Record> {
-        let mut record = Record::new();
-        None
-    }
+impl<R: Read> Iterator for XmlRecordIterator<R> {
+    type Item = Result<Record, String>;
 
-    fn read_next_from_file(&mut self, context: &mut XmlParseContext) -> Option<Record> {
+    fn next(&mut self) -> Option<Self::Item> {
         let mut record = Record::new();
-
-        let reader = match &mut self.reader {
-            Some(r) => r,
-            None => {
-                return None;
-            }
-        };
+        let mut context = XmlParseContext::new();
 
         loop {
-            match reader.next() {
-                Ok(evt) => {
-                    if XmlEvent::EndDocument == evt {
-                        // All done.
-                        return None;
-                    }
+            match self.reader.next() {
+                Ok(XmlEvent::EndDocument) => return None,
 
-                    match Record::handle_xml_read_event(&mut record, context, evt) {
-                        Ok(_) => {
-                            if context.record_complete {
-                                return Some(record);
-                            }
-                        }
-                        Err(e) => {
-                            // Can't return an Err() from an iterator, so
-                            // log the issue and carry on.
-                            eprintln!("Error processing XML: {e}");
-                            return None;
+                Ok(evt) => match Record::handle_xml_read_event(&mut record, &mut context, evt) {
+                    Ok(_) => {
+                        if context.record_complete {
+                            return Some(Ok(record));
                         }
                     }
-                }
-                Err(e) => {
-                    // Can't return an Err() from an iterator, so
-                    // log the issue and carry on.
-                    eprintln!("Error processing XML: {e}");
-                    return None;
-                }
+                    Err(e) => {
+                        // Drain the rest of this record's events so the
+                        // next call starts cleanly at the next <record>
+                        // instead of treating its leftover fragments as
+                        // the start of a new one.
+                        self.skip_to_end_of_record();
+                        return Some(Err(e));
+                    }
+                },
+
+                Err(e) => return Some(Err(format!("Error parsing MARCXML: {e}"))),
             }
         }
     }
 }
 
 impl Record {
-    /// Returns an iterator over the XML file which emits Records.
-    pub fn from_xml_file(filename: &str) -> Result<XmlRecordIterator, String> {
-        Ok(XmlRecordIterator::from_file(filename)?)
+    /// Returns an iterator that lazily parses each `<record>` out of
+    /// a MARCXML file, which may be a lone `<record>` or a
+    /// `<collection>` of many.
+    pub fn from_xml_file(filename: &str) -> Result<XmlRecordIterator<File>, String> {
+        let file = File::open(filename)
+            .map_err(|e| format!("Cannot read MARCXML file: {filename} {e}"))?;
+
+        Ok(XmlRecordIterator::new(file))
     }
 
-    /// TODO ITERATOR
-    /// Returns a single Record from the XML.
+    /// Returns the first Record found in the XML.
     pub fn from_xml(xml: &str) -> Result<Self, String> {
         let parser = EventReader::new(xml.as_bytes());
         let mut record = Record::new();
-
-        let mut context = XmlParseContext {
-            in_cfield: false,
-            in_subfield: false,
-            in_leader: false,
-            record_complete: false,
-        };
+        let mut context = XmlParseContext::new();
 
         for evt_res in parser {
             match evt_res {
@@ -206,12 +182,10 @@ impl Record {
                         .filter(|a| a.name.local_name.eq("tag"))
                         .next()
                     {
-                        record
-                            .control_fields
-                            .push(Controlfield::new(&t.value, None)?);
+                        record.control_fields.push(Controlfield::new(&t.value)?);
                         context.in_cfield = true;
                     } else {
-                        return Err(format!("Controlfield has no tag"));
+                        return Err("Controlfield has no tag".to_string());
                     }
                 }
 
@@ -223,7 +197,7 @@ impl Record {
                     {
                         record.fields.push(Field::new(&t.value)?);
                     } else {
-                        return Err(format!("Data field has no tag"));
+                        return Err("Data field has no tag".to_string());
                     }
 
                     if let Some(ind) = attributes
@@ -254,7 +228,7 @@ impl Record {
                             .filter(|a| a.name.local_name.eq("code"))
                             .next()
                         {
-                            if let Ok(sf) = Subfield::new(&code.value, None) {
+                            if let Ok(sf) = Subfield::new(&code.value) {
                                 context.in_subfield = true;
                                 field.subfields.push(sf);
                             }
@@ -343,7 +317,7 @@ impl Record {
             xml += &format!(
                 r#"<controlfield tag="{}">{}</controlfield>"#,
                 escape_xml(&cfield.tag.content),
-                escape_xml(&cfield.content),
+                escape_xml(cfield.content.as_deref().unwrap_or("")),
             );
         }
 
@@ -355,8 +329,8 @@ impl Record {
             xml += &format!(
                 r#"<datafield tag="{}" ind1="{}" ind2="{}">"#,
                 escape_xml(&field.tag.content),
-                escape_xml(&field.ind1.to_string()),
-                escape_xml(&field.ind2.to_string())
+                escape_xml(field.ind1.as_str()),
+                escape_xml(field.ind2.as_str())
             );
 
             for sf in &field.subfields {
@@ -365,7 +339,7 @@ impl Record {
                 xml += &format!(
                     r#"<subfield code="{}">{}</subfield>"#,
                     &escape_xml(&sf.code),
-                    &escape_xml(&sf.content)
+                    &escape_xml(sf.content.as_deref().unwrap_or(""))
                 );
             }
 
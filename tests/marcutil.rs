@@ -1,3 +1,7 @@
+use marcutil::marc8::Encoding;
+use marcutil::query::QueryMatch;
+use marcutil::query::QueryMatchMut;
+use marcutil::xml::XmlRecordIterator;
 use marcutil::Tag;
 use marcutil::Field;
 use marcutil::Indicator;
@@ -5,6 +9,7 @@ use marcutil::Controlfield;
 use marcutil::Subfield;
 use marcutil::Leader;
 use marcutil::Record;
+use std::io::Cursor;
 
 
 // Avoiding newlines / formatting for testing purposes.
@@ -22,6 +27,30 @@ fn breaker_round_trip() {
     assert_eq!(breaker, breaker2);
 }
 
+#[test]
+fn breaker_blank_indicator_and_escaped_dollar() {
+
+    let mut record = Record::new();
+    let mut field = Field::new("245").expect("New field");
+    // ind1/ind2 default to unset, which to_breaker renders as "\".
+
+    let mut sf = Subfield::new("a").expect("New subfield");
+    sf.set_content("Rock $ roll");
+    field.subfields.push(sf);
+    record.fields.push(field);
+
+    let breaker = record.to_breaker();
+    let record2 = Record::from_breaker(&breaker).expect("Built from breaker");
+
+    assert!(record2.get_fields("245")[0].ind2.content.is_none());
+    assert_eq!(record2.get_values("245", "a"), vec!["Rock $ roll"]);
+}
+
+#[test]
+fn breaker_rejects_non_ascii_indicators() {
+    assert!(Record::from_breaker("245\u{e9}\u{e9}").is_err());
+}
+
 #[test]
 fn xml_round_trip() {
 
@@ -31,6 +60,44 @@ fn xml_round_trip() {
     assert_eq!(MARC_XML, xml);
 }
 
+#[test]
+fn xml_streaming_collection() {
+
+    let record_body = &MARC_XML[MARC_XML.find("<record").unwrap()..];
+    let collection = format!(
+        r#"<?xml version="1.0"?><collection>{record_body}{record_body}</collection>"#
+    );
+
+    let mut iter = XmlRecordIterator::new(Cursor::new(collection));
+
+    let record1 = iter.next().expect("First record").expect("Valid record");
+    assert_eq!(record1.get_values("028", "a"), vec!["HL50498721"]);
+
+    let record2 = iter.next().expect("Second record").expect("Valid record");
+    assert_eq!(record2.get_values("028", "a"), vec!["HL50498721"]);
+
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn xml_streaming_skips_malformed_record() {
+
+    let record_body = &MARC_XML[MARC_XML.find("<record").unwrap()..];
+    let malformed = r#"<record><controlfield>no tag here</controlfield></record>"#;
+    let collection = format!(
+        r#"<?xml version="1.0"?><collection>{malformed}{record_body}</collection>"#
+    );
+
+    let mut iter = XmlRecordIterator::new(Cursor::new(collection));
+
+    assert!(iter.next().expect("First slot").is_err());
+
+    let record = iter.next().expect("Second slot").expect("Valid record");
+    assert_eq!(record.get_values("028", "a"), vec!["HL50498721"]);
+
+    assert!(iter.next().is_none());
+}
+
 #[test]
 fn all_round_trip() {
 
@@ -43,6 +110,295 @@ fn all_round_trip() {
     assert_eq!(MARC_XML, xml);
 }
 
+#[test]
+fn json_round_trip() {
+
+    let record = Record::from_xml(MARC_XML).expect("Created record from XML");
+    let json = record.to_json().expect("To JSON");
+
+    let record2 = Record::from_json(&json).expect("Built from JSON");
+    let xml = record2.to_xml().expect("To XML");
+
+    assert_eq!(MARC_XML, xml);
+}
+
+#[test]
+fn json_rejects_multi_key_field() {
+    let malformed = r#"{"leader": "", "fields": [{"245": "x", "246": "y"}]}"#;
+    assert!(Record::from_json(malformed).is_err());
+}
+
+#[test]
+fn json_rejects_non_string_subfield_content() {
+    let malformed =
+        r#"{"leader": "", "fields": [{"245": {"ind1": " ", "ind2": " ", "subfields": [{"a": 1}]}}]}"#;
+    assert!(Record::from_json(malformed).is_err());
+}
+
+#[test]
+fn add_control_and_data_fields() {
+    let mut record = Record::new();
+
+    record
+        .add_control_field("005", "123123123123")
+        .expect("Added control field");
+    record
+        .add_data_field("650", vec!["a", "Hobbits", "b", "Fiction"])
+        .expect("Added data field");
+
+    assert_eq!(
+        record.get_control_fields("005")[0].content.as_deref(),
+        Some("123123123123")
+    );
+    assert_eq!(record.get_values("650", "a"), vec!["Hobbits"]);
+    assert_eq!(record.get_values("650", "b"), vec!["Fiction"]);
+
+    assert!(record.add_data_field("650", vec!["a"]).is_err());
+}
+
+#[test]
+fn field_and_record_accessors() {
+
+    let mut record = Record::from_xml(MARC_XML).expect("Created record from XML");
+
+    let field = record.get_field_mut("028").expect("Found 028 field");
+    assert_eq!(
+        field.first_subfield("a").unwrap().content.as_deref(),
+        Some("HL50498721")
+    );
+
+    let matches = record.get_fields_matching("01?").expect("Valid tag pattern");
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].tag.content, "010");
+
+    let matches = record.get_fields_matching("02X").expect("Valid tag pattern");
+    assert_eq!(matches.len(), 4);
+
+    assert!(record.get_fields_matching("02").is_err());
+
+    let mut new_field = Field::new("500").expect("New field");
+    new_field.add_subfield("a", "A note").expect("Added subfield");
+    record.add_field(new_field);
+
+    let tags: Vec<&str> = record.fields.iter().map(|f| f.tag.content.as_str()).collect();
+    let mut sorted_tags = tags.clone();
+    sorted_tags.sort();
+    assert_eq!(tags, sorted_tags);
+
+    let removed = record.remove_field("500").expect("Removed 500 field");
+    assert_eq!(removed.tag.content, "500");
+    assert!(record.get_field_mut("500").is_none());
+
+    // add_data_field must keep the same sortedness invariant as
+    // add_field, since both can be used on the same record.
+    record
+        .add_data_field("100", vec!["a", "An Author"])
+        .expect("Added data field");
+
+    let tags: Vec<&str> = record.fields.iter().map(|f| f.tag.content.as_str()).collect();
+    let mut sorted_tags = tags.clone();
+    sorted_tags.sort();
+    assert_eq!(tags, sorted_tags);
+}
+
+#[test]
+fn query_spec() {
+
+    let mut record = Record::from_xml(MARC_XML).expect("Created record from XML");
+
+    match record.query("020$a").expect("Valid field spec") {
+        QueryMatch::Subfields(subfields) => assert_eq!(subfields.len(), 2),
+        _ => panic!("Expected Subfields match"),
+    }
+
+    match record.query("028 32$a").expect("Valid field spec") {
+        QueryMatch::Subfields(subfields) => {
+            assert_eq!(subfields[0].content.as_deref(), Some("HL50498721"));
+        }
+        _ => panic!("Expected Subfields match"),
+    }
+
+    match record.query("008").expect("Valid field spec") {
+        QueryMatch::Control(cf) => assert!(cf.content.is_some()),
+        _ => panic!("Expected Control match"),
+    }
+
+    match record.query("028 _1$a").expect("Valid field spec") {
+        QueryMatch::Subfields(subfields) => assert!(subfields.is_empty()),
+        _ => panic!("Expected Subfields match"),
+    }
+
+    // query and query_mut must agree on subfield order when the spec
+    // names codes out of the subfields' physical storage order.
+    match record.query("028$b$a").expect("Valid field spec") {
+        QueryMatch::Subfields(subfields) => {
+            let codes: Vec<&str> = subfields.iter().map(|sf| sf.code.as_str()).collect();
+            assert_eq!(codes, vec!["b", "a"]);
+        }
+        _ => panic!("Expected Subfields match"),
+    }
+
+    if let QueryMatchMut::Subfields(subfields) = record.query_mut("028$b$a").expect("Valid field spec") {
+        let codes: Vec<&str> = subfields.iter().map(|sf| sf.code.as_str()).collect();
+        assert_eq!(codes, vec!["b", "a"]);
+    }
+
+    if let QueryMatchMut::Subfields(mut subfields) = record.query_mut("028$b").expect("Valid field spec") {
+        subfields[0].set_content("Changed Publisher");
+    }
+
+    assert_eq!(record.get_values("028", "b"), vec!["Changed Publisher"]);
+
+    // A spec naming more than one subfield code must also work mutably,
+    // without requiring two simultaneous mutable borrows of the field.
+    if let QueryMatchMut::Subfields(mut subfields) =
+        record.query_mut("028$a$b").expect("Valid field spec")
+    {
+        for sf in subfields.iter_mut() {
+            sf.set_content("Changed");
+        }
+    }
+
+    assert_eq!(record.get_values("028", "a"), vec!["Changed"]);
+    assert_eq!(record.get_values("028", "b"), vec!["Changed"]);
+}
+
+#[test]
+fn binary_round_trip() {
+
+    let record = Record::from_xml(MARC_XML).expect("Created record from XML");
+    let bytes = record.to_binary().expect("To Binary");
+
+    let record2 = Record::from_binary(&bytes).expect("Built from binary");
+    let xml = record2.to_xml().expect("To XML");
+
+    // to_binary() recomputes the leader's record-length and
+    // base-address fields from the actual field content, so they
+    // won't match MARC_XML's original (much larger) fixture leader;
+    // everything else should still round-trip byte for byte.
+    let recomputed_leader = &record2.leader.as_ref().unwrap().content;
+    let expected_xml = MARC_XML.replacen(
+        "07649cim a2200913 i 4500",
+        recomputed_leader,
+        1,
+    );
+
+    assert_eq!(expected_xml, xml);
+}
+
+#[test]
+fn binary_rejects_base_address_with_no_room_for_directory_terminator() {
+    // leader: record length "00026", base address of data "00024"
+    // (== LEADER_SIZE), leaving no room for even an empty directory's
+    // own terminator byte before it.
+    let mut bytes = vec![b' '; 26];
+    bytes[0..5].copy_from_slice(b"00026");
+    bytes[12..17].copy_from_slice(b"00024");
+    bytes[25] = 0x1D;
+
+    assert!(Record::from_binary(&bytes).is_err());
+}
+
+#[test]
+fn leader_accessors_and_repair() {
+
+    let mut record = Record::from_xml(MARC_XML).expect("Created record from XML");
+
+    assert_eq!(record.leader.as_ref().unwrap().bibliographic_level(), 'm');
+
+    record.repair_leader();
+
+    let leader = record.leader.as_ref().unwrap();
+    assert_eq!(leader.indicator_count(), '2');
+    assert_eq!(leader.subfield_code_count(), '2');
+    assert_eq!(leader.entry_map(), "4500");
+
+    // to_binary() must recompute length/base-address regardless of
+    // whatever the parsed leader happened to say.
+    let bytes = record.to_binary().expect("To Binary");
+    let rebuilt = Record::from_binary(&bytes).expect("Built from binary");
+
+    assert_eq!(
+        rebuilt.leader.as_ref().unwrap().record_length().unwrap(),
+        bytes.len()
+    );
+
+    assert!(leader.clone().set_bibliographic_level('\u{20AC}').is_err());
+}
+
+#[test]
+fn marc8_round_trip() {
+
+    let mut record = Record::new();
+    record
+        .set_leader("00000cam a2200000 a 4500")
+        .expect("Set leader");
+
+    let mut field = Field::new("245").expect("New field");
+    let mut sf = Subfield::new("a").expect("New subfield");
+    sf.set_content("Caf\u{00E9} \u{00D8}resund");
+    field.subfields.push(sf);
+    record.fields.push(field);
+
+    let bytes = record
+        .to_binary_with_encoding(Encoding::Marc8)
+        .expect("To MARC-8 binary");
+
+    let record2 =
+        Record::from_binary_with_encoding(&bytes, Encoding::Marc8).expect("From MARC-8 binary");
+
+    assert_eq!(
+        record2.get_values("245", "a"),
+        vec!["Caf\u{00E9} \u{00D8}resund"]
+    );
+
+    // from_binary() should auto-detect MARC-8 from the leader's
+    // character coding scheme without being told explicitly.
+    let record3 = Record::from_binary(&bytes).expect("Auto-detected MARC-8 binary");
+    assert_eq!(record3.get_values("245", "a"), vec!["Caf\u{00E9} \u{00D8}resund"]);
+}
+
+#[test]
+fn marc8_rejects_unrepresentable_character() {
+
+    let mut record = Record::new();
+    record
+        .set_leader("00000cam a2200000 a 4500")
+        .expect("Set leader");
+
+    let mut field = Field::new("245").expect("New field");
+    let mut sf = Subfield::new("a").expect("New subfield");
+    sf.set_content("\u{4E2D}\u{6587}");
+    field.subfields.push(sf);
+    record.fields.push(field);
+
+    assert!(record.to_binary_with_encoding(Encoding::Marc8).is_err());
+}
+
+#[test]
+fn latin1_round_trip() {
+
+    let mut record = Record::new();
+    record
+        .set_leader("00000cam a2200000 a 4500")
+        .expect("Set leader");
+
+    let mut field = Field::new("245").expect("New field");
+    let mut sf = Subfield::new("a").expect("New subfield");
+    sf.set_content("Caf\u{00E9}");
+    field.subfields.push(sf);
+    record.fields.push(field);
+
+    let bytes = record
+        .to_binary_with_encoding(Encoding::Latin1)
+        .expect("To Latin-1 binary");
+
+    let record2 =
+        Record::from_binary_with_encoding(&bytes, Encoding::Latin1).expect("From Latin-1 binary");
+
+    assert_eq!(record2.get_values("245", "a"), vec!["Caf\u{00E9}"]);
+}
+
 #[test]
 fn odd_records() {
     let op = Record::from_xml("<record/>");